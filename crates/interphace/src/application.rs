@@ -1,32 +1,49 @@
-use wayland_client::{globals::registry_queue_init, Connection, EventQueue};
+use calloop::{EventLoop, LoopHandle, LoopSignal};
+use calloop_wayland_source::WaylandSource;
+use wayland_client::{globals::registry_queue_init, Connection};
 
-use crate::{backend::Backend, window::Window};
+use crate::{backend::Backend, error::PhrameError, window::Window};
 
 pub struct Application {
     pub backend: Backend,
-    event_queue: EventQueue<Backend>
+    event_loop: EventLoop<'static, Backend>,
 }
 
 impl Application {
-    pub fn new() -> Self {
-        let connection = Connection::connect_to_env().expect("Failed to connect to Wayland server");
+    pub fn new() -> Result<Self, PhrameError> {
+        let connection = Connection::connect_to_env()
+            .map_err(|e| PhrameError::NoWaylandConnection(e.to_string()))?;
 
-        let (global_list, mut event_queue) = registry_queue_init(&connection).unwrap();
+        let (global_list, event_queue) = registry_queue_init(&connection).unwrap();
 
-        let backend = Backend::new(global_list, event_queue.handle());
-        
-        Self {
-            backend,
-            event_queue
-        }
+        let event_loop: EventLoop<Backend> =
+            EventLoop::try_new().expect("Failed to create event loop");
+
+        let backend = Backend::new(
+            global_list,
+            &connection,
+            event_queue.handle(),
+            event_loop.handle(),
+            event_loop.get_signal(),
+        )?;
+
+        WaylandSource::new(connection, event_queue)
+            .insert(event_loop.handle())
+            .expect("Failed to insert Wayland source into event loop");
+
+        Ok(Self { backend, event_loop })
+    }
+
+    /// A handle for registering additional timer, FD or idle sources alongside the
+    /// Wayland connection.
+    pub fn loop_handle(&self) -> LoopHandle<'static, Backend> {
+        self.event_loop.handle()
     }
 
     pub fn run(&mut self) {
-        loop {
-            self.event_queue
-                .blocking_dispatch(&mut self.backend)
-                .unwrap();
-        }
+        self.event_loop
+            .run(None, &mut self.backend, |_backend| {})
+            .expect("Event loop failed");
     }
 
     pub fn create_window(&mut self) -> &Window {
@@ -1,62 +1,157 @@
+use calloop::{LoopHandle, LoopSignal};
 use smithay_client_toolkit::{
     compositor::{CompositorHandler, CompositorState},
-    delegate_compositor, delegate_layer, delegate_output, delegate_registry, delegate_seat,
-    delegate_xdg_shell, delegate_xdg_window,
+    delegate_compositor, delegate_keyboard, delegate_layer, delegate_output, delegate_registry,
+    delegate_pointer, delegate_seat, delegate_shm, delegate_xdg_shell, delegate_xdg_window,
     output::{OutputHandler, OutputState},
+    reexports::wayland_protocols::wp::{
+        fractional_scale::v1::client::wp_fractional_scale_manager_v1::WpFractionalScaleManagerV1,
+        viewporter::client::wp_viewporter::WpViewporter,
+    },
     registry::{ProvidesRegistryState, RegistryState},
     registry_handlers,
-    seat::{Capability, SeatHandler, SeatState},
+    seat::{
+        keyboard::{KeyEvent, KeyboardHandler, Modifiers, RepeatInfo},
+        pointer::{PointerEvent, PointerEventKind, PointerHandler},
+        Capability, SeatHandler, SeatState,
+    },
     shell::{
         wlr_layer::{LayerShell, LayerShellHandler, LayerSurface, LayerSurfaceConfigure},
         xdg::{
             window::{Window as XdgWindow, WindowDecorations, WindowHandler},
             XdgShell,
         },
+        WaylandSurface,
     },
+    shm::{Shm, ShmHandler},
 };
 use wayland_client::{
     globals::GlobalList,
-    protocol::{wl_seat::WlSeat, wl_surface::WlSurface},
+    protocol::{wl_keyboard::WlKeyboard, wl_pointer::WlPointer, wl_seat::WlSeat, wl_surface::WlSurface},
     Connection, QueueHandle,
 };
 
-use crate::window::Window;
+use crate::{
+    error::PhrameError,
+    gl_renderer::GlRenderer,
+    input::{InputEvent, PointerButton},
+    keyboard::KeyRepeat,
+    renderer::RenderTarget,
+    scale::Scale,
+    shm_renderer::ShmRenderer,
+    text_input::TextInput,
+    window::Window,
+};
 
 pub struct Backend {
     queue_handle: QueueHandle<Backend>,
+    loop_handle: LoopHandle<'static, Backend>,
     compositor_state: CompositorState,
     registry_state: RegistryState,
     seat_state: SeatState,
     output_state: OutputState,
     xdg_shell: XdgShell,
     layer_shell: LayerShell,
+    fractional_scale_manager: Option<WpFractionalScaleManagerV1>,
+    viewporter: Option<WpViewporter>,
     windows: Vec<Window>,
+    loop_signal: LoopSignal,
+    keyboard: Option<WlKeyboard>,
+    keyboard_focus: Option<WlSurface>,
+    key_repeat: KeyRepeat,
+    text_input: TextInput,
+    pointer: Option<WlPointer>,
+    pointer_focus: Option<WlSurface>,
+    shm: Shm,
+    gl_available: bool,
 }
 
 impl Backend {
-    pub fn new(global_list: GlobalList, queue_handle: QueueHandle<Backend>) -> Self {
-        let compositor_state =
-            CompositorState::bind(&global_list, &queue_handle).expect("Compositor not available");
+    pub fn new(
+        global_list: GlobalList,
+        connection: &Connection,
+        queue_handle: QueueHandle<Backend>,
+        loop_handle: LoopHandle<'static, Backend>,
+        loop_signal: LoopSignal,
+    ) -> Result<Self, PhrameError> {
+        let compositor_state = CompositorState::bind(&global_list, &queue_handle)
+            .map_err(|_| PhrameError::WaylandGlobalMissing("wl_compositor"))?;
 
         let registry_state = RegistryState::new(&global_list);
         let seat_state = SeatState::new(&global_list, &queue_handle);
         let output_state = OutputState::new(&global_list, &queue_handle);
 
-        let xdg_shell =
-            XdgShell::bind(&global_list, &queue_handle).expect("Xdg shell not available");
-        let layer_shell =
-            LayerShell::bind(&global_list, &queue_handle).expect("Layer shell not available");
+        let xdg_shell = XdgShell::bind(&global_list, &queue_handle)
+            .map_err(|_| PhrameError::WaylandGlobalMissing("xdg_wm_base"))?;
+        let layer_shell = LayerShell::bind(&global_list, &queue_handle)
+            .map_err(|_| PhrameError::WaylandGlobalMissing("zwlr_layer_shell_v1"))?;
+
+        // Both are optional: compositors without them just get the integer
+        // `wl_surface` scale fallback.
+        let fractional_scale_manager = global_list
+            .bind::<WpFractionalScaleManagerV1, _, _>(&queue_handle, 1..=1, ())
+            .ok();
+        let viewporter = global_list
+            .bind::<WpViewporter, _, _>(&queue_handle, 1..=1, ())
+            .ok();
+
+        let shm = Shm::bind(&global_list, &queue_handle)
+            .map_err(|_| PhrameError::WaylandGlobalMissing("wl_shm"))?;
+
+        // Probed once up front: cheaper than retrying a failed GL context on
+        // every window, and lets `create_renderer` go straight to the
+        // `wl_shm` renderer when no usable EGL config exists.
+        let gl_available = GlRenderer::is_available(connection);
+        if !gl_available {
+            println!("No usable EGL config found, falling back to wl_shm rendering");
+        }
 
-        Self {
+        Ok(Self {
             queue_handle,
+            loop_handle,
             compositor_state,
             registry_state,
             seat_state,
             output_state,
             xdg_shell,
             layer_shell,
+            fractional_scale_manager,
+            viewporter,
             windows: Vec::new(),
+            loop_signal,
+            keyboard: None,
+            keyboard_focus: None,
+            key_repeat: KeyRepeat::default(),
+            text_input: TextInput::default(),
+            pointer: None,
+            pointer_focus: None,
+            shm,
+            gl_available,
+        })
+    }
+
+    /// Create the renderer for a not-yet-mapped `wl_surface`, sized to its
+    /// initial `width`x`height`. Attempts the GL-backed renderer first when
+    /// the compositor's display looked like it exposed a usable EGL config
+    /// at startup; if that construction fails for any reason (the upfront
+    /// probe can't catch every way a context/surface can fail to come up),
+    /// falls back to the `wl_shm` renderer instead of taking the whole
+    /// process down with it.
+    pub fn create_renderer(&self, surface: &WlSurface, width: u32, height: u32) -> Box<dyn RenderTarget> {
+        if self.gl_available {
+            match GlRenderer::try_new(surface) {
+                Ok(renderer) => {
+                    println!("Using GL rendering backend");
+                    return Box::new(renderer);
+                }
+                Err(err) => {
+                    println!("GL renderer construction failed ({err}), falling back to wl_shm rendering");
+                }
+            }
         }
+
+        println!("Using wl_shm rendering backend");
+        Box::new(ShmRenderer::new(&self.shm, surface.clone(), width, height))
     }
 
     pub fn create_surface(&self) -> WlSurface {
@@ -68,9 +163,45 @@ impl Backend {
             .create_window(surface, WindowDecorations::None, &self.queue_handle)
     }
 
+    /// Bind the per-surface fractional-scale/viewport objects for a
+    /// not-yet-mapped `wl_surface`, if the compositor advertises the globals.
+    pub fn bind_scale(&self, scale: &mut Scale, surface: &WlSurface, initial_logical_size: (i32, i32)) {
+        scale.bind(
+            self.fractional_scale_manager.as_ref(),
+            self.viewporter.as_ref(),
+            surface,
+            &self.queue_handle,
+            initial_logical_size,
+        );
+    }
+
     pub fn add_window(&mut self, window: Window) {
         self.windows.push(window);
     }
+
+    fn window_for_surface(&mut self, surface: &WlSurface) -> Option<&mut Window> {
+        self.windows
+            .iter_mut()
+            .find(|w| w.xdg_window.wl_surface() == surface)
+    }
+
+    fn fractional_scale_changed(&mut self, surface: &WlSurface, scale_120: u32, qh: &QueueHandle<Self>) {
+        if let Some(window) = self.window_for_surface(surface) {
+            window.scale_mut().set_fractional(scale_120);
+            window.scale_changed();
+            window.request_redraw(qh);
+        }
+    }
+
+    /// Route a decoded input event to the `Window` owning `surface`, and
+    /// redraw it if the window reports the event changed anything visible.
+    fn dispatch_input(&mut self, surface: &WlSurface, event: InputEvent, qh: &QueueHandle<Self>) {
+        if let Some(window) = self.window_for_surface(surface) {
+            if window.handle_input(event) {
+                window.request_redraw(qh);
+            }
+        }
+    }
 }
 
 delegate_xdg_shell!(Backend);
@@ -78,6 +209,7 @@ delegate_xdg_window!(Backend);
 impl WindowHandler for Backend {
     fn request_close(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _window: &XdgWindow) {
         println!("Window wants to close");
+        self.loop_signal.stop();
     }
 
     fn configure(
@@ -88,15 +220,18 @@ impl WindowHandler for Backend {
         configure: smithay_client_toolkit::shell::xdg::window::WindowConfigure,
         _serial: u32,
     ) {
-        // find thew window that has the given xdg window and call draw on it
+        // `new_size` is in logical (surface-local) coordinates; the window
+        // scales it to a buffer size itself.
+        let (logical_width, logical_height) = configure.new_size.unwrap_or((800, 600));
+
         self.windows
             .iter_mut()
             .find(|w| &w.xdg_window == window)
-            .map(|w| w.draw(qh));
+            .map(|w| {
+                w.configure_size(logical_width, logical_height);
+                w.request_redraw(qh);
+            });
 
-
-        // call draw on each window
-        //self.windows.iter_mut().for_each(|w| w.draw(qh));
         println!("Window configured to: {:?}", configure);
     }
 }
@@ -114,21 +249,48 @@ impl CompositorHandler for Backend {
     fn scale_factor_changed(
         &mut self,
         _conn: &Connection,
-        _qh: &QueueHandle<Self>,
-        _surface: &WlSurface,
-        _new_factor: i32,
+        qh: &QueueHandle<Self>,
+        surface: &WlSurface,
+        new_factor: i32,
     ) {
-        // Not needed for this example.
+        if let Some(window) = self.window_for_surface(surface) {
+            // Fractional scale, once reported, takes priority; only apply
+            // the integer scale fallback when no `wp_fractional_scale_v1`
+            // has preempted it.
+            if !window.scale().is_fractional() {
+                // With a `wp_viewport` bound, integer scale must be driven
+                // purely through its destination rect (already set to the
+                // logical size in `apply_scale`): also setting
+                // `wl_surface.buffer_scale` here double-applies the scale,
+                // since the viewport's default source is the whole buffer
+                // divided by that same buffer_scale.
+                if !window.scale().has_viewport() {
+                    surface.set_buffer_scale(new_factor);
+                }
+                window.scale_mut().set_integer(new_factor);
+                window.scale_changed();
+                window.request_redraw(qh);
+            }
+        }
     }
 
     fn frame(
         &mut self,
         _conn: &Connection,
-        _qh: &QueueHandle<Self>,
-        _surface: &WlSurface,
+        qh: &QueueHandle<Self>,
+        surface: &WlSurface,
         _time: u32,
     ) {
-        //self.draw(&qh);
+        if let Some(window) = self.window_for_surface(surface) {
+            window.frame_done(qh);
+        }
+    }
+}
+
+delegate_shm!(Backend);
+impl ShmHandler for Backend {
+    fn shm_state(&mut self) -> &mut Shm {
+        &mut self.shm
     }
 }
 
@@ -174,10 +336,25 @@ impl SeatHandler for Backend {
     fn new_capability(
         &mut self,
         _conn: &Connection,
-        _: &QueueHandle<Self>,
-        _: WlSeat,
-        _: Capability,
+        qh: &QueueHandle<Self>,
+        seat: WlSeat,
+        capability: Capability,
     ) {
+        if capability == Capability::Keyboard && self.keyboard.is_none() {
+            let keyboard = self
+                .seat_state
+                .get_keyboard(qh, &seat, None)
+                .expect("Failed to create keyboard");
+            self.keyboard = Some(keyboard);
+        }
+
+        if capability == Capability::Pointer && self.pointer.is_none() {
+            let pointer = self
+                .seat_state
+                .get_pointer(qh, &seat)
+                .expect("Failed to create pointer");
+            self.pointer = Some(pointer);
+        }
     }
 
     fn remove_capability(
@@ -185,13 +362,204 @@ impl SeatHandler for Backend {
         _conn: &Connection,
         _: &QueueHandle<Self>,
         _: WlSeat,
-        _: Capability,
+        capability: Capability,
     ) {
+        if capability == Capability::Keyboard {
+            if let Some(keyboard) = self.keyboard.take() {
+                self.key_repeat.cancel(&self.loop_handle);
+                keyboard.release();
+            }
+            self.keyboard_focus = None;
+        }
+
+        if capability == Capability::Pointer {
+            if let Some(pointer) = self.pointer.take() {
+                pointer.release();
+            }
+            self.pointer_focus = None;
+        }
     }
 
     fn remove_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: WlSeat) {}
 }
 
+delegate_keyboard!(Backend);
+impl KeyboardHandler for Backend {
+    fn enter(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _keyboard: &WlKeyboard,
+        surface: &WlSurface,
+        _serial: u32,
+        _raw: &[u32],
+        _keysyms: &[u32],
+    ) {
+        self.keyboard_focus = Some(surface.clone());
+    }
+
+    fn leave(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _keyboard: &WlKeyboard,
+        surface: &WlSurface,
+        _serial: u32,
+    ) {
+        if self.keyboard_focus.as_ref() == Some(surface) {
+            self.keyboard_focus = None;
+        }
+        self.key_repeat.cancel(&self.loop_handle);
+    }
+
+    fn press_key(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        keyboard: &WlKeyboard,
+        _serial: u32,
+        event: KeyEvent,
+    ) {
+        let text = self.text_input.feed(event.keysym, event.utf8.as_deref());
+        println!("Key press: {event:?} text={text:?}");
+
+        if let Some(surface) = self.keyboard_focus.clone() {
+            self.dispatch_input(
+                &surface,
+                InputEvent::KeyPress {
+                    keysym: event.keysym.raw(),
+                    text,
+                },
+                qh,
+            );
+        }
+
+        self.key_repeat
+            .arm(&self.loop_handle, qh, keyboard.clone(), event);
+    }
+
+    fn release_key(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        _keyboard: &WlKeyboard,
+        _serial: u32,
+        event: KeyEvent,
+    ) {
+        println!("Key release: {event:?}");
+        self.key_repeat.cancel(&self.loop_handle);
+
+        if let Some(surface) = self.keyboard_focus.clone() {
+            self.dispatch_input(
+                &surface,
+                InputEvent::KeyRelease {
+                    keysym: event.keysym.raw(),
+                },
+                qh,
+            );
+        }
+    }
+
+    fn update_modifiers(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _keyboard: &WlKeyboard,
+        _serial: u32,
+        _modifiers: Modifiers,
+    ) {
+    }
+
+    fn update_repeat_info(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _keyboard: &WlKeyboard,
+        info: RepeatInfo,
+    ) {
+        self.key_repeat.set_info(info);
+    }
+}
+
+impl Backend {
+    /// Invoked by the key-repeat timer armed in `press_key`; re-emits the held
+    /// key for as long as it stays down.
+    fn repeat_key(&mut self, qh: &QueueHandle<Self>, _keyboard: &WlKeyboard, event: &KeyEvent) {
+        let text = self.text_input.feed(event.keysym, event.utf8.as_deref());
+        println!("Key press (repeat): {event:?} text={text:?}");
+
+        if let Some(surface) = self.keyboard_focus.clone() {
+            self.dispatch_input(
+                &surface,
+                InputEvent::KeyPress {
+                    keysym: event.keysym.raw(),
+                    text,
+                },
+                qh,
+            );
+        }
+    }
+}
+
+delegate_pointer!(Backend);
+impl PointerHandler for Backend {
+    fn pointer_frame(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        _pointer: &WlPointer,
+        events: &[PointerEvent],
+    ) {
+        for event in events {
+            match event.kind {
+                PointerEventKind::Enter { .. } => {
+                    self.pointer_focus = Some(event.surface.clone());
+                }
+                PointerEventKind::Leave { .. } => {
+                    if self.pointer_focus.as_ref() == Some(&event.surface) {
+                        self.pointer_focus = None;
+                    }
+                }
+                PointerEventKind::Motion { .. } => {
+                    let (x, y) = event.position;
+                    self.dispatch_input(&event.surface, InputEvent::PointerMotion { x, y }, qh);
+                }
+                PointerEventKind::Press { button, .. } => {
+                    self.dispatch_input(
+                        &event.surface,
+                        InputEvent::PointerButton {
+                            button: PointerButton::from(button),
+                            pressed: true,
+                        },
+                        qh,
+                    );
+                }
+                PointerEventKind::Release { button, .. } => {
+                    self.dispatch_input(
+                        &event.surface,
+                        InputEvent::PointerButton {
+                            button: PointerButton::from(button),
+                            pressed: false,
+                        },
+                        qh,
+                    );
+                }
+                PointerEventKind::Axis {
+                    horizontal,
+                    vertical,
+                    ..
+                } => {
+                    self.dispatch_input(
+                        &event.surface,
+                        InputEvent::PointerAxis { horizontal, vertical },
+                        qh,
+                    );
+                }
+            }
+        }
+    }
+}
+
 delegate_layer!(Backend);
 impl LayerShellHandler for Backend {
     fn closed(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _layer: &LayerSurface) {}
@@ -0,0 +1,338 @@
+use std::{
+    fs::{File, OpenOptions},
+    os::fd::{AsFd, AsRawFd, BorrowedFd},
+    os::unix::fs::OpenOptionsExt,
+};
+
+use drm::control::{connector, crtc, framebuffer, Device as ControlDevice, Event, Mode, PageFlipFlags};
+use drm::Device as BasicDevice;
+use gbm::{BufferObject, BufferObjectFlags, Device as GbmDevice, Format as GbmFormat, Surface as GbmSurface};
+use glutin::{
+    api::egl::{context::PossiblyCurrentContext, display::Display, surface::Surface},
+    config::{Api, ConfigSurfaceTypes, ConfigTemplateBuilder, GetGlConfig},
+    context::{ContextApi, ContextAttributesBuilder},
+    prelude::{
+        GlConfig, GlDisplay, NotCurrentGlContextSurfaceAccessor,
+        PossiblyCurrentContextGlSurfaceAccessor,
+    },
+    surface::{GlSurface, SurfaceAttributesBuilder, WindowSurface},
+};
+use raw_window_handle::{GbmDisplayHandle, GbmWindowHandle, RawDisplayHandle, RawWindowHandle};
+use skia_safe::{
+    gpu::{
+        gl::{Format, FramebufferInfo},
+        BackendRenderTarget, DirectContext, SurfaceOrigin,
+    },
+    Canvas, ColorType, Surface as SkiaSurface,
+};
+
+use crate::renderer::{DamageRect, RenderTarget};
+
+/// A `/dev/dri/cardN` fd, wrapped so it can implement the `drm`/`gbm` crates'
+/// device marker traits.
+struct Card(File);
+
+impl AsFd for Card {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.as_fd()
+    }
+}
+
+impl BasicDevice for Card {}
+impl ControlDevice for Card {}
+
+/// Renders directly to a DRM/KMS output rather than through a Wayland
+/// compositor: for kiosk, TTY or otherwise compositor-less use. Implements
+/// `RenderTarget` like the Wayland-backed renderers, but ignores the damage
+/// it's given `present`ed since there's no partial-scanout equivalent of
+/// `wl_surface.damage_buffer` — every frame repaints and presents the whole
+/// buffer.
+///
+/// Keeps at most two GBM buffers in flight: the one currently scanned out
+/// and, while a page flip is in flight, the one it's replacing (held until
+/// the kernel's flip-complete event confirms the switch). Restores the
+/// CRTC's prior mode on drop.
+pub struct DrmRenderer {
+    gbm: GbmDevice<Card>,
+    gbm_surface: GbmSurface<()>,
+    possibly_current_context: PossiblyCurrentContext,
+    window_surface: Surface<WindowSurface>,
+    skia_surface: SkiaSurface,
+    crtc: crtc::Handle,
+    connector: connector::Handle,
+    mode: Mode,
+    saved_crtc: crtc::Info,
+    scanned_out: Option<(BufferObject<()>, framebuffer::Handle)>,
+    /// The previously scanned-out buffer, kept alive until its flip-complete
+    /// event arrives so it isn't released while still on screen.
+    pending_release: Option<(BufferObject<()>, framebuffer::Handle)>,
+    crtc_armed: bool,
+}
+
+impl DrmRenderer {
+    /// Open `path` (typically `/dev/dri/card0`), become DRM master, pick the
+    /// first connected connector's preferred mode and its current CRTC, and
+    /// build a GBM-backed EGL/Skia surface sized to that mode.
+    pub fn new(path: &str) -> Self {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            // `drain_flip_events` relies on reads not blocking when no event
+            // is queued yet.
+            .custom_flags(libc::O_NONBLOCK)
+            .open(path)
+            .unwrap_or_else(|e| panic!("Failed to open {path}: {e}"));
+        let card = Card(file);
+        card.acquire_master_lock()
+            .expect("Failed to become DRM master");
+
+        let resources = card
+            .resource_handles()
+            .expect("Failed to get DRM resource handles");
+
+        let connector_info = resources
+            .connectors()
+            .iter()
+            .filter_map(|&handle| card.get_connector(handle, false).ok())
+            .find(|info| info.state() == connector::State::Connected)
+            .expect("No connected connector found");
+
+        // The first mode a connector reports is its preferred one.
+        let mode = *connector_info
+            .modes()
+            .first()
+            .expect("Connector has no modes");
+
+        let encoder = connector_info
+            .current_encoder()
+            .and_then(|handle| card.get_encoder(handle).ok())
+            .expect("Connector has no current encoder");
+        let crtc = encoder.crtc().expect("Encoder has no attached CRTC");
+        let saved_crtc = card
+            .get_crtc(crtc)
+            .expect("Failed to read current CRTC state");
+
+        let gbm = GbmDevice::new(card).expect("Failed to create GBM device");
+
+        let (width, height) = mode.size();
+        let gbm_surface = gbm
+            .create_surface::<()>(
+                width as u32,
+                height as u32,
+                GbmFormat::Xrgb8888,
+                BufferObjectFlags::SCANOUT | BufferObjectFlags::RENDERING,
+            )
+            .expect("Failed to create GBM surface");
+
+        let mut gbm_display_handle = GbmDisplayHandle::empty();
+        gbm_display_handle.gbm_device = gbm.as_raw() as *mut _;
+        let raw_display_handle = RawDisplayHandle::Gbm(gbm_display_handle);
+
+        let mut gbm_window_handle = GbmWindowHandle::empty();
+        gbm_window_handle.gbm_surface = gbm_surface.as_raw() as *mut _;
+        let raw_window_handle = RawWindowHandle::Gbm(gbm_window_handle);
+
+        let display = unsafe { Display::new(raw_display_handle) }
+            .expect("Failed to initialize GBM EGL platform");
+
+        let config_template = ConfigTemplateBuilder::default()
+            .compatible_with_native_window(raw_window_handle)
+            .with_surface_type(ConfigSurfaceTypes::WINDOW)
+            .with_api(Api::GLES2 | Api::GLES3 | Api::OPENGL)
+            .build();
+
+        let display_config = unsafe { display.find_configs(config_template) }
+            .unwrap()
+            .next()
+            .expect("No available configs");
+
+        let gl_context_attributes = ContextAttributesBuilder::default()
+            .with_context_api(ContextApi::OpenGl(None))
+            .build(Some(raw_window_handle));
+        let gles_context_attributes = ContextAttributesBuilder::default()
+            .with_context_api(ContextApi::Gles(None))
+            .build(Some(raw_window_handle));
+
+        let not_current_context =
+            unsafe { display.create_context(&display_config, &gl_context_attributes) }
+                .or_else(|_| unsafe {
+                    display.create_context(&display_config, &gles_context_attributes)
+                })
+                .expect("Failed to create context");
+
+        let surface_attributes = SurfaceAttributesBuilder::<WindowSurface>::default().build(
+            raw_window_handle,
+            std::num::NonZeroU32::new(width as u32).unwrap(),
+            std::num::NonZeroU32::new(height as u32).unwrap(),
+        );
+
+        let window_surface =
+            unsafe { display.create_window_surface(&display_config, &surface_attributes) }
+                .expect("Failed to create surface");
+
+        let possibly_current_context = not_current_context
+            .make_current(&window_surface)
+            .expect("Failed to make context current");
+
+        let skia_surface = initialize_skia(&window_surface, &possibly_current_context);
+
+        Self {
+            gbm,
+            gbm_surface,
+            possibly_current_context,
+            window_surface,
+            skia_surface,
+            crtc,
+            connector: connector_info.handle(),
+            mode,
+            saved_crtc,
+            scanned_out: None,
+            pending_release: None,
+            crtc_armed: false,
+        }
+    }
+
+    fn make_current(&mut self) {
+        self.possibly_current_context
+            .make_current(&self.window_surface)
+            .expect("Failed to make context current");
+    }
+
+    /// Release the buffer a previously-queued page flip replaced, once its
+    /// flip-complete event shows up on the DRM fd. Non-blocking: only
+    /// processes events already buffered by the kernel.
+    fn drain_flip_events(&mut self) {
+        if self.pending_release.is_none() {
+            return;
+        }
+
+        let Ok(events) = self.gbm.receive_events() else {
+            return;
+        };
+
+        for event in events {
+            if let Event::PageFlip(_) = event {
+                if let Some((old_bo, old_fb)) = self.pending_release.take() {
+                    let _ = self.gbm.destroy_framebuffer(old_fb);
+                    drop(old_bo);
+                }
+            }
+        }
+    }
+}
+
+impl RenderTarget for DrmRenderer {
+    /// DRM/KMS scans out at the fixed mode chosen in `new`; there's no
+    /// equivalent of resizing a window, so this is a no-op.
+    fn resize(&mut self, _width: u32, _height: u32) {}
+
+    /// Paint one frame and scan it out: lock the just-rendered GBM buffer,
+    /// wrap it in a DRM framebuffer, and either `drmModeSetCrtc` (first
+    /// frame) or queue a page flip. First blocks until any outstanding page
+    /// flip has completed and drains its flip-complete event, which is where
+    /// the buffer the *previous* flip replaced actually gets released — not
+    /// here — so a buffer still on screen is never unlocked out from under
+    /// the scanout, and no more than two buffers are ever locked. `damage`
+    /// is ignored: a full-buffer scanout has no partial-present equivalent.
+    fn present(&mut self, _damage: &[DamageRect], paint: &mut dyn FnMut(&Canvas)) {
+        while self.pending_release.is_some() {
+            let mut pollfd = libc::pollfd {
+                fd: self.gbm.as_fd().as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            // SAFETY: `pollfd` is a single valid entry for the whole call.
+            unsafe { libc::poll(&mut pollfd, 1, -1) };
+            self.drain_flip_events();
+        }
+
+        self.make_current();
+        paint(self.skia_surface.canvas());
+        self.skia_surface.flush_and_submit();
+        self.window_surface
+            .swap_buffers(&self.possibly_current_context)
+            .expect("Failed to swap buffers");
+
+        let bo = self
+            .gbm_surface
+            .lock_front_buffer()
+            .expect("Failed to lock front GBM buffer");
+        let fb = self
+            .gbm
+            .add_framebuffer(&bo, 24, 32)
+            .expect("Failed to add DRM framebuffer");
+
+        if !self.crtc_armed {
+            self.gbm
+                .set_crtc(self.crtc, Some(fb), (0, 0), &[self.connector], Some(self.mode))
+                .expect("Failed to set initial CRTC mode");
+            self.crtc_armed = true;
+            self.scanned_out = Some((bo, fb));
+        } else {
+            self.gbm
+                .page_flip(self.crtc, fb, PageFlipFlags::EVENT, None)
+                .expect("Failed to queue page flip");
+
+            // The buffer we just replaced stays locked until its
+            // flip-complete event confirms the kernel has switched away
+            // from it; `drain_flip_events` releases it then.
+            let superseded = self.scanned_out.replace((bo, fb));
+            debug_assert!(
+                self.pending_release.is_none(),
+                "a page flip was queued while one was already outstanding"
+            );
+            self.pending_release = superseded;
+        }
+    }
+}
+
+impl Drop for DrmRenderer {
+    fn drop(&mut self) {
+        // Restore whatever mode was active before we took over the CRTC.
+        let _ = self.gbm.set_crtc(
+            self.crtc,
+            self.saved_crtc.framebuffer(),
+            self.saved_crtc.position(),
+            &[self.connector],
+            self.saved_crtc.mode(),
+        );
+    }
+}
+
+fn initialize_skia(
+    window_surface: &Surface<WindowSurface>,
+    possibly_current_context: &PossiblyCurrentContext,
+) -> SkiaSurface {
+    let mut gr_direct_context =
+        DirectContext::new_gl(None, None).expect("Failed to create Skia DirectContext");
+
+    let width = window_surface.width().expect("Window surface has no width");
+    let height = window_surface
+        .height()
+        .expect("Window surface has no height");
+
+    let sample_count = possibly_current_context.config().num_samples();
+    let stencil_bits = possibly_current_context.config().stencil_size();
+
+    let framebuffer_info = FramebufferInfo {
+        fboid: Default::default(),
+        format: Format::RGBA8.into(),
+    };
+
+    let gr_backend_render_target = BackendRenderTarget::new_gl(
+        (width as i32, height as i32),
+        sample_count as usize,
+        stencil_bits as usize,
+        framebuffer_info,
+    );
+
+    SkiaSurface::from_backend_render_target(
+        &mut gr_direct_context,
+        &gr_backend_render_target,
+        SurfaceOrigin::BottomLeft,
+        ColorType::RGBA8888,
+        None,
+        None,
+    )
+    .expect("Failed to create Skia surface")
+}
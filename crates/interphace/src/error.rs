@@ -0,0 +1,46 @@
+use std::fmt;
+
+/// Errors surfaced from backend/context setup: GL/EGL context and surface
+/// creation, Skia backend wiring, and required Wayland globals. Kept
+/// separate from the inner GL/Wayland error types (which don't all
+/// implement `std::error::Error`) so callers get a small, matchable set of
+/// variants plus a human-readable cause instead of a bare panic.
+#[derive(Debug)]
+pub enum PhrameError {
+    /// The display exposed no EGL config matching what we asked for.
+    NoEglConfig,
+    /// The EGL display couldn't be initialized, or a GL context couldn't be
+    /// created (for every API variant we tried) or made current.
+    ContextCreation(String),
+    /// The EGL window surface couldn't be created.
+    SurfaceCreation(String),
+    /// Wiring the context/surface into a Skia `DirectContext`/`Surface` failed.
+    SkiaBackend(String),
+    /// A Wayland global this backend requires wasn't advertised by the
+    /// compositor.
+    WaylandGlobalMissing(&'static str),
+    /// No Wayland compositor could be reached at all, as opposed to one that
+    /// answered but didn't advertise a required global. Callers can use this
+    /// to distinguish "fall back to DRM/KMS" from "this compositor is
+    /// missing something it shouldn't be".
+    NoWaylandConnection(String),
+}
+
+impl fmt::Display for PhrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PhrameError::NoEglConfig => write!(f, "no matching EGL config available"),
+            PhrameError::ContextCreation(reason) => write!(f, "failed to create GL context: {reason}"),
+            PhrameError::SurfaceCreation(reason) => write!(f, "failed to create window surface: {reason}"),
+            PhrameError::SkiaBackend(reason) => write!(f, "failed to set up Skia backend: {reason}"),
+            PhrameError::WaylandGlobalMissing(name) => {
+                write!(f, "required Wayland global `{name}` is not available")
+            }
+            PhrameError::NoWaylandConnection(reason) => {
+                write!(f, "failed to connect to a Wayland compositor: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PhrameError {}
@@ -0,0 +1,396 @@
+use std::{collections::VecDeque, num::NonZeroU32};
+
+use glutin::{
+    api::egl::{context::PossiblyCurrentContext, display::Display, surface::Surface},
+    config::{Api, ConfigSurfaceTypes, ConfigTemplateBuilder, GetGlConfig},
+    context::{ContextApi, ContextAttributesBuilder},
+    prelude::{
+        GlConfig, GlDisplay, NotCurrentGlContextSurfaceAccessor,
+        PossiblyCurrentContextGlSurfaceAccessor,
+    },
+    surface::{GlSurface, Rect as GlRect, SurfaceAttributesBuilder, WindowSurface},
+};
+use raw_window_handle::{
+    RawDisplayHandle, RawWindowHandle, WaylandDisplayHandle, WaylandWindowHandle,
+};
+use skia_safe::{
+    gpu::{
+        gl::{Format, FramebufferInfo},
+        BackendRenderTarget, DirectContext, SurfaceOrigin,
+    },
+    Canvas, ColorType, IRect, Region, Surface as SkiaSurface,
+};
+use wayland_client::{protocol::wl_surface::WlSurface, Connection, Proxy};
+
+use crate::{
+    error::PhrameError,
+    renderer::{clamp_to_surface, DamageRect, RenderTarget},
+};
+
+/// How many past frames' damage we keep around to reconstruct the region a
+/// stale back buffer is missing. EGL implementations typically cycle
+/// through 2-3 buffers, so this comfortably covers any buffer age they'll
+/// report.
+const DAMAGE_HISTORY_LEN: usize = 4;
+
+/// The GL-backed Skia surface: an EGL context and window surface, plus the
+/// Skia `DirectContext`/`Surface` wrapping its default framebuffer.
+pub struct GlRenderer {
+    wl_surface: WlSurface,
+    possibly_current_context: PossiblyCurrentContext,
+    window_surface: Surface<WindowSurface>,
+    skia_surface: SkiaSurface,
+    /// Damage of the last [`DAMAGE_HISTORY_LEN`] frames, most recent first.
+    damage_history: VecDeque<Vec<DamageRect>>,
+}
+
+impl GlRenderer {
+    pub fn try_new(wl_surface: &WlSurface) -> Result<Self, PhrameError> {
+        let (possibly_current_context, window_surface) = initialize_gl_context(wl_surface)?;
+
+        println!("GL context initialized");
+        let skia_surface = initialize_skia(&window_surface, &possibly_current_context)?;
+
+        Ok(Self {
+            wl_surface: wl_surface.clone(),
+            possibly_current_context,
+            window_surface,
+            skia_surface,
+            damage_history: VecDeque::with_capacity(DAMAGE_HISTORY_LEN),
+        })
+    }
+
+    /// Cheaply check whether the compositor's Wayland display exposes a
+    /// usable EGL config, without creating a context or surface. Used at
+    /// startup to choose between the GL and SHM renderers.
+    pub fn is_available(connection: &Connection) -> bool {
+        let mut display_handle = WaylandDisplayHandle::empty();
+        display_handle.display = connection.backend().display_ptr() as *mut _;
+        let raw_display_handle = RawDisplayHandle::Wayland(display_handle);
+
+        let Ok(display) = (unsafe { Display::new(raw_display_handle) }) else {
+            return false;
+        };
+
+        let config_template = ConfigTemplateBuilder::default()
+            .with_surface_type(ConfigSurfaceTypes::WINDOW)
+            .with_api(Api::GLES2 | Api::GLES3 | Api::OPENGL)
+            .build();
+
+        unsafe { display.find_configs(config_template) }
+            .map(|mut configs| configs.next().is_some())
+            .unwrap_or(false)
+    }
+
+    fn make_current(&mut self) {
+        self.possibly_current_context
+            .make_current(&self.window_surface)
+            .expect("Failed to make context current");
+    }
+
+    fn swap_buffers(&mut self) {
+        self.window_surface
+            .swap_buffers(&self.possibly_current_context)
+            .expect("Failed to swap buffers");
+    }
+
+    /// Present only the given buffer-local damage rectangles, via
+    /// `EGL_KHR_swap_buffers_with_damage`. Falls back to a full-surface swap
+    /// when the extension isn't available.
+    fn swap_buffers_with_damage(&mut self, rects: &[GlRect]) {
+        if self
+            .window_surface
+            .swap_buffers_with_damage(&self.possibly_current_context, rects)
+            .is_err()
+        {
+            self.swap_buffers();
+        }
+    }
+
+    /// The region that actually needs repainting this frame: the current
+    /// frame's own damage, plus the damage of however many past frames the
+    /// back buffer (per `EGL_BUFFER_AGE_EXT`) hasn't seen yet. An age of `0`
+    /// means the extension is unsupported or the buffer's contents are
+    /// otherwise unknown, so the whole surface is repainted.
+    fn redraw_region(&mut self, current: &[DamageRect], width: i32, height: i32) -> Vec<IRect> {
+        let age = self
+            .window_surface
+            .buffer_age(&self.possibly_current_context);
+
+        let rects = union_stale_damage(age, &self.damage_history, current, width, height);
+
+        self.damage_history.push_front(current.to_vec());
+        self.damage_history.truncate(DAMAGE_HISTORY_LEN);
+
+        rects
+    }
+}
+
+/// The union of `current`'s damage with however many past frames (from
+/// `history`, most recent first) the back buffer of the given `age` hasn't
+/// seen yet, clamped to the buffer bounds. An `age` of `0`, or one exceeding
+/// how much history is kept, means the buffer's contents are unknown, so the
+/// whole surface is returned instead.
+fn union_stale_damage(
+    age: i32,
+    history: &VecDeque<Vec<DamageRect>>,
+    current: &[DamageRect],
+    width: i32,
+    height: i32,
+) -> Vec<IRect> {
+    let rects: Vec<DamageRect> = if age == 0 || age as usize > history.len() {
+        vec![DamageRect {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }]
+    } else {
+        history
+            .iter()
+            .take(age as usize)
+            .flatten()
+            .chain(current)
+            .copied()
+            .collect()
+    };
+
+    rects
+        .into_iter()
+        .map(|r| {
+            let clamped = clamp_to_surface(r, width, height);
+            IRect::new(
+                clamped.x,
+                clamped.y,
+                clamped.x + clamped.width,
+                clamped.y + clamped.height,
+            )
+        })
+        .collect()
+}
+
+impl RenderTarget for GlRenderer {
+    /// Resize the glutin surface to the new dimensions and recreate the
+    /// Skia backend render target to match.
+    fn resize(&mut self, width: u32, height: u32) {
+        let width = NonZeroU32::new(width).unwrap_or(NonZeroU32::new(1).unwrap());
+        let height = NonZeroU32::new(height).unwrap_or(NonZeroU32::new(1).unwrap());
+
+        self.window_surface
+            .resize(&self.possibly_current_context, width, height);
+
+        self.skia_surface = initialize_skia(&self.window_surface, &self.possibly_current_context)
+            .expect("Failed to recreate Skia surface after resize");
+
+        // The history no longer matches a buffer this size.
+        self.damage_history.clear();
+    }
+
+    fn present(&mut self, damage: &[DamageRect], paint: &mut dyn FnMut(&Canvas)) {
+        self.make_current();
+
+        let width = self.window_surface.width().unwrap_or(0) as i32;
+        let height = self.window_surface.height().unwrap_or(0) as i32;
+
+        // Empty `damage` means the whole buffer changed.
+        let full = [DamageRect {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }];
+        let damage = if damage.is_empty() { &full[..] } else { damage };
+
+        let redraw_rects = self.redraw_region(damage, width, height);
+        let mut redraw_region = Region::new();
+        redraw_region.set_rects(&redraw_rects);
+
+        let canvas = self.skia_surface.canvas();
+        canvas.save();
+        canvas.clip_region(&redraw_region, None);
+        paint(canvas);
+        canvas.restore();
+        self.skia_surface.flush_and_submit();
+
+        let rects: Vec<GlRect> = if damage.is_empty() {
+            vec![GlRect {
+                x: 0,
+                y: 0,
+                width,
+                height,
+            }]
+        } else {
+            damage
+                .iter()
+                .map(|d| GlRect {
+                    x: d.x,
+                    y: d.y,
+                    width: d.width,
+                    height: d.height,
+                })
+                .collect()
+        };
+
+        for rect in &rects {
+            self.wl_surface
+                .damage_buffer(rect.x, rect.y, rect.width, rect.height);
+        }
+
+        // `wl_surface.damage_buffer` and the Skia canvas both use a
+        // top-left, y-down origin, but `swap_buffers_with_damage` is an EGL
+        // call and so expects bottom-left, y-up rects. Flip before handing
+        // them to EGL; otherwise partial swaps present the wrong band.
+        let egl_rects: Vec<GlRect> = rects
+            .iter()
+            .map(|rect| GlRect {
+                x: rect.x,
+                y: height - rect.y - rect.height,
+                width: rect.width,
+                height: rect.height,
+            })
+            .collect();
+        self.swap_buffers_with_damage(&egl_rects);
+    }
+}
+
+fn initialize_skia(
+    window_surface: &Surface<WindowSurface>,
+    possibly_current_context: &PossiblyCurrentContext,
+) -> Result<SkiaSurface, PhrameError> {
+    let mut gr_direct_context = DirectContext::new_gl(None, None)
+        .ok_or_else(|| PhrameError::SkiaBackend("failed to create Skia DirectContext".into()))?;
+
+    let width = window_surface
+        .width()
+        .ok_or_else(|| PhrameError::SkiaBackend("window surface has no width".into()))?;
+    let height = window_surface
+        .height()
+        .ok_or_else(|| PhrameError::SkiaBackend("window surface has no height".into()))?;
+
+    let sample_count = possibly_current_context.config().num_samples();
+    let stencil_bits = possibly_current_context.config().stencil_size();
+
+    let framebuffer_info = FramebufferInfo {
+        fboid: Default::default(),
+        format: Format::RGBA8.into(),
+    };
+
+    let gr_backend_render_target = BackendRenderTarget::new_gl(
+        (width as i32, height as i32),
+        sample_count as usize,
+        stencil_bits as usize,
+        framebuffer_info,
+    );
+
+    SkiaSurface::from_backend_render_target(
+        &mut gr_direct_context,
+        &gr_backend_render_target,
+        SurfaceOrigin::BottomLeft,
+        ColorType::RGBA8888,
+        None,
+        None,
+    )
+    .ok_or_else(|| PhrameError::SkiaBackend("failed to create Skia surface from backend render target".into()))
+}
+
+fn initialize_gl_context(
+    wl_surface: &WlSurface,
+) -> Result<(PossiblyCurrentContext, Surface<WindowSurface>), PhrameError> {
+    let mut wayland_display_handle = WaylandDisplayHandle::empty();
+    wayland_display_handle.display = wl_surface
+        .backend()
+        .upgrade()
+        .ok_or_else(|| PhrameError::ContextCreation("Wayland connection has been closed".into()))?
+        .display_ptr() as *mut _;
+    let raw_display_handle = RawDisplayHandle::Wayland(wayland_display_handle);
+
+    let mut wayland_window_handle = WaylandWindowHandle::empty();
+    wayland_window_handle.surface = wl_surface.id().as_ptr() as *mut _;
+    let raw_window_handle = RawWindowHandle::Wayland(wayland_window_handle);
+
+    let display = unsafe { Display::new(raw_display_handle) }
+        .map_err(|e| PhrameError::ContextCreation(format!("failed to initialize EGL platform: {e}")))?;
+
+    let config_template = ConfigTemplateBuilder::default()
+        .compatible_with_native_window(raw_window_handle)
+        .with_surface_type(ConfigSurfaceTypes::WINDOW)
+        .with_api(Api::GLES2 | Api::GLES3 | Api::OPENGL)
+        .build();
+
+    let display_config = unsafe { display.find_configs(config_template) }
+        .map_err(|_| PhrameError::NoEglConfig)?
+        .next()
+        .ok_or(PhrameError::NoEglConfig)?;
+
+    let gl_context_attributes = ContextAttributesBuilder::default()
+        .with_context_api(ContextApi::OpenGl(None))
+        .build(Some(raw_window_handle));
+
+    let gles_context_attributes = ContextAttributesBuilder::default()
+        .with_context_api(ContextApi::Gles(None))
+        .build(Some(raw_window_handle));
+
+    let not_current_context =
+        unsafe { display.create_context(&display_config, &gl_context_attributes) }
+            .or_else(|_| unsafe {
+                display.create_context(&display_config, &gles_context_attributes)
+            })
+            .map_err(|e| PhrameError::ContextCreation(e.to_string()))?;
+
+    let surface_attributes = SurfaceAttributesBuilder::<WindowSurface>::default().build(
+        raw_window_handle,
+        NonZeroU32::new(100).unwrap(),
+        NonZeroU32::new(100).unwrap(),
+    );
+
+    let window_surface = unsafe { display.create_window_surface(&display_config, &surface_attributes) }
+        .map_err(|e| PhrameError::SurfaceCreation(e.to_string()))?;
+
+    let possibly_current_context = not_current_context
+        .make_current(&window_surface)
+        .map_err(|e| PhrameError::ContextCreation(format!("failed to make context current: {e}")))?;
+
+    Ok((possibly_current_context, window_surface))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::union_stale_damage;
+    use crate::renderer::DamageRect;
+
+    fn rect(x: i32, y: i32, width: i32, height: i32) -> DamageRect {
+        DamageRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn zero_age_repaints_the_whole_buffer() {
+        let history = VecDeque::new();
+        let rects = union_stale_damage(0, &history, &[rect(0, 0, 5, 5)], 100, 80);
+        assert_eq!(rects.len(), 1);
+        assert_eq!((rects[0].width(), rects[0].height()), (100, 80));
+    }
+
+    #[test]
+    fn age_beyond_history_repaints_the_whole_buffer() {
+        let mut history = VecDeque::new();
+        history.push_front(vec![rect(0, 0, 5, 5)]);
+        let rects = union_stale_damage(2, &history, &[rect(0, 0, 5, 5)], 100, 80);
+        assert_eq!(rects.len(), 1);
+        assert_eq!((rects[0].width(), rects[0].height()), (100, 80));
+    }
+
+    #[test]
+    fn age_within_history_unions_current_with_the_stale_frames() {
+        let mut history = VecDeque::new();
+        history.push_front(vec![rect(0, 0, 5, 5)]);
+        let rects = union_stale_damage(1, &history, &[rect(10, 10, 5, 5)], 100, 80);
+        assert_eq!(rects.len(), 2);
+    }
+}
@@ -0,0 +1,51 @@
+use smithay_client_toolkit::seat::pointer::{AxisScroll, BTN_LEFT, BTN_MIDDLE, BTN_RIGHT};
+
+/// A decoded keyboard or pointer event, routed to the focused `Window` by
+/// [`Backend`](crate::backend::Backend) once it has resolved which surface
+/// the originating `wl_keyboard`/`wl_pointer` is currently focused on.
+#[derive(Clone, Debug)]
+pub enum InputEvent {
+    KeyPress { keysym: u32, text: Option<String> },
+    KeyRelease { keysym: u32 },
+    PointerMotion { x: f64, y: f64 },
+    PointerButton { button: PointerButton, pressed: bool },
+    PointerAxis { horizontal: AxisScroll, vertical: AxisScroll },
+}
+
+/// The subset of `wl_pointer` button codes phrame currently distinguishes;
+/// anything else is reported as `Other`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointerButton {
+    Left,
+    Right,
+    Middle,
+    Other(u32),
+}
+
+impl From<u32> for PointerButton {
+    fn from(code: u32) -> Self {
+        match code {
+            BTN_LEFT => PointerButton::Left,
+            BTN_RIGHT => PointerButton::Right,
+            BTN_MIDDLE => PointerButton::Middle,
+            other => PointerButton::Other(other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_button_codes_map_to_named_variants() {
+        assert_eq!(PointerButton::from(BTN_LEFT), PointerButton::Left);
+        assert_eq!(PointerButton::from(BTN_RIGHT), PointerButton::Right);
+        assert_eq!(PointerButton::from(BTN_MIDDLE), PointerButton::Middle);
+    }
+
+    #[test]
+    fn unknown_button_code_maps_to_other() {
+        assert_eq!(PointerButton::from(0x1234), PointerButton::Other(0x1234));
+    }
+}
@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+use calloop::{
+    timer::{TimeoutAction, Timer},
+    LoopHandle, RegistrationToken,
+};
+use smithay_client_toolkit::seat::keyboard::{KeyEvent, RepeatInfo};
+use wayland_client::{protocol::wl_keyboard::WlKeyboard, QueueHandle};
+use xkbcommon::xkb::{keysyms, Keysym};
+
+use crate::backend::Backend;
+
+/// Whether `keysym` is a modifier key (Shift, Control, Alt, Super, Caps/Num
+/// Lock, ...). The compositor still sends `press_key`/`release_key` for
+/// these, but they shouldn't drive the repeat timer: holding Shift isn't a
+/// request to keep "pressing" Shift.
+fn is_modifier_keysym(keysym: Keysym) -> bool {
+    matches!(
+        keysym.raw(),
+        keysyms::KEY_Shift_L
+            | keysyms::KEY_Shift_R
+            | keysyms::KEY_Control_L
+            | keysyms::KEY_Control_R
+            | keysyms::KEY_Alt_L
+            | keysyms::KEY_Alt_R
+            | keysyms::KEY_Meta_L
+            | keysyms::KEY_Meta_R
+            | keysyms::KEY_Super_L
+            | keysyms::KEY_Super_R
+            | keysyms::KEY_Hyper_L
+            | keysyms::KEY_Hyper_R
+            | keysyms::KEY_Caps_Lock
+            | keysyms::KEY_Shift_Lock
+            | keysyms::KEY_Num_Lock
+            | keysyms::KEY_ISO_Level3_Shift
+            | keysyms::KEY_ISO_Level5_Shift
+    )
+}
+
+/// Re-arms a calloop timer off the compositor's repeat rate/delay so a held
+/// key keeps producing presses after the initial one, mirroring SCTK's
+/// `RepeatKind`: a fixed rate/delay pair, or disabled entirely when the
+/// compositor reports a rate of zero.
+pub struct KeyRepeat {
+    info: RepeatInfo,
+    timer: Option<RegistrationToken>,
+}
+
+impl Default for KeyRepeat {
+    fn default() -> Self {
+        Self {
+            info: RepeatInfo::Repeat {
+                rate: 25,
+                delay: 600,
+            },
+            timer: None,
+        }
+    }
+}
+
+impl KeyRepeat {
+    pub fn set_info(&mut self, info: RepeatInfo) {
+        self.info = info;
+    }
+
+    /// Tear down the timer for whatever key was previously held.
+    pub fn cancel(&mut self, loop_handle: &LoopHandle<'static, Backend>) {
+        if let Some(timer) = self.timer.take() {
+            loop_handle.remove(timer);
+        }
+    }
+
+    /// Replace the timer with one for a freshly pressed, non-modifier key.
+    pub fn arm(
+        &mut self,
+        loop_handle: &LoopHandle<'static, Backend>,
+        queue_handle: &QueueHandle<Backend>,
+        keyboard: WlKeyboard,
+        event: KeyEvent,
+    ) {
+        self.cancel(loop_handle);
+
+        if is_modifier_keysym(event.keysym) {
+            return;
+        }
+
+        let (rate, delay) = match self.info {
+            RepeatInfo::Repeat { rate, delay } if rate > 0 => (rate, delay),
+            _ => return,
+        };
+
+        let interval = Duration::from_millis(1000 / rate as u64);
+        let queue_handle = queue_handle.clone();
+
+        let timer = Timer::from_duration(Duration::from_millis(delay as u64));
+        let registration = loop_handle
+            .insert_source(timer, move |_deadline, _, backend| {
+                backend.repeat_key(&queue_handle, &keyboard, &event);
+                TimeoutAction::ToDuration(interval)
+            })
+            .expect("Failed to arm key repeat timer");
+
+        self.timer = Some(registration);
+    }
+}
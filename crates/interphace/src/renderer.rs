@@ -0,0 +1,78 @@
+use skia_safe::Canvas;
+
+/// A buffer-local damage rectangle. Kept independent of any one backend's
+/// own rectangle type (glutin's EGL `Rect`, the raw ints `wl_surface`
+/// expects) so callers can stay agnostic to which `RenderTarget` they hold.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DamageRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// A surface pixels can be painted into and presented to, independent of
+/// how that presentation happens: compositing through a Wayland
+/// `wl_surface` (`GlRenderer`, `ShmRenderer`) or scanning directly out to a
+/// DRM/KMS CRTC (`DrmRenderer`). Each implementation owns whatever handle
+/// presentation needs (a `wl_surface`, a GBM surface, ...), so the trait
+/// itself stays free of any one backend's presentation API.
+pub trait RenderTarget {
+    fn resize(&mut self, width: u32, height: u32);
+
+    /// Paint one frame and present it. `damage` lists the buffer-local
+    /// rectangles that changed since the last present; empty means the
+    /// whole buffer must be repainted. Implementations that can only ever
+    /// present the full buffer (there's no partial-scanout equivalent of
+    /// `wl_surface.damage_buffer`) are free to ignore it.
+    fn present(&mut self, damage: &[DamageRect], paint: &mut dyn FnMut(&Canvas));
+}
+
+/// Clip a buffer-local damage rectangle to `0..width`/`0..height`, shrinking
+/// it (never going negative) if it extends past either edge.
+pub fn clamp_to_surface(rect: DamageRect, width: i32, height: i32) -> DamageRect {
+    let x = rect.x.clamp(0, width);
+    let y = rect.y.clamp(0, height);
+    let right = (rect.x + rect.width).clamp(0, width);
+    let bottom = (rect.y + rect.height).clamp(0, height);
+
+    DamageRect {
+        x,
+        y,
+        width: (right - x).max(0),
+        height: (bottom - y).max(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{clamp_to_surface, DamageRect};
+
+    fn rect(x: i32, y: i32, width: i32, height: i32) -> DamageRect {
+        DamageRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn rect_within_bounds_is_unchanged() {
+        let r = rect(10, 10, 20, 20);
+        assert_eq!(clamp_to_surface(r, 100, 100), r);
+    }
+
+    #[test]
+    fn rect_past_edges_clips_without_going_negative() {
+        let clamped = clamp_to_surface(rect(90, 90, 50, 50), 100, 100);
+        assert_eq!(clamped, rect(90, 90, 10, 10));
+    }
+
+    #[test]
+    fn rect_entirely_past_edges_has_zero_size() {
+        let clamped = clamp_to_surface(rect(150, 150, 20, 20), 100, 100);
+        assert_eq!(clamped.width, 0);
+        assert_eq!(clamped.height, 0);
+    }
+}
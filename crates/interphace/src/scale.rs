@@ -0,0 +1,172 @@
+use smithay_client_toolkit::reexports::wayland_protocols::wp::{
+    fractional_scale::v1::client::{
+        wp_fractional_scale_manager_v1::WpFractionalScaleManagerV1,
+        wp_fractional_scale_v1::{Event as FractionalScaleEvent, WpFractionalScaleV1},
+    },
+    viewporter::client::{
+        wp_viewport::WpViewport,
+        wp_viewporter::WpViewporter,
+    },
+};
+use wayland_client::{protocol::wl_surface::WlSurface, Connection, Dispatch, QueueHandle};
+
+use crate::backend::Backend;
+
+/// Per-surface scale tracking. `wp_fractional_scale_v1` reports the
+/// preferred scale in 120ths of an integer; falls back to the compositor's
+/// integer `wl_surface` scale when the fractional-scale protocol isn't
+/// bound.
+#[derive(Default)]
+pub struct Scale {
+    fractional_120: Option<u32>,
+    integer: i32,
+    viewport: Option<WpViewport>,
+}
+
+impl Scale {
+    /// Bind the per-surface fractional-scale and viewport objects, if the
+    /// compositor advertises the globals, and point the viewport at the
+    /// surface's initial logical size so it renders correctly even if the
+    /// first `xdg_surface.configure` hasn't arrived yet.
+    pub fn bind(
+        &mut self,
+        manager: Option<&WpFractionalScaleManagerV1>,
+        viewporter: Option<&WpViewporter>,
+        surface: &WlSurface,
+        qh: &QueueHandle<Backend>,
+        initial_logical_size: (i32, i32),
+    ) {
+        self.integer = 1;
+        surface.set_buffer_scale(1);
+
+        if let (Some(manager), Some(viewporter)) = (manager, viewporter) {
+            manager.get_fractional_scale(surface, qh, surface.clone());
+            let viewport = viewporter.get_viewport(surface, qh, ());
+            let (logical_width, logical_height) = initial_logical_size;
+            viewport.set_destination(logical_width, logical_height);
+            self.viewport = Some(viewport);
+        }
+    }
+
+    /// The effective scale factor to apply to logical coordinates before
+    /// drawing.
+    pub fn factor(&self) -> f64 {
+        match self.fractional_120 {
+            Some(scale_120) => scale_120 as f64 / 120.0,
+            None => self.integer.max(1) as f64,
+        }
+    }
+
+    /// True once a fractional scale has been reported for this surface; the
+    /// integer `wl_surface.set_buffer_scale` fallback should then be left
+    /// alone.
+    pub fn is_fractional(&self) -> bool {
+        self.fractional_120.is_some()
+    }
+
+    /// True once a `wp_viewport` has been bound for this surface. While one
+    /// is active, integer scale must be driven purely through its
+    /// destination rectangle: a `wp_viewport` with no explicit source takes
+    /// the whole buffer as its source, so also calling
+    /// `wl_surface.set_buffer_scale` double-applies the scale and crops the
+    /// surface to the wrong region.
+    pub fn has_viewport(&self) -> bool {
+        self.viewport.is_some()
+    }
+
+    pub fn set_integer(&mut self, integer: i32) {
+        self.integer = integer;
+    }
+
+    pub(crate) fn set_fractional(&mut self, scale_120: u32) {
+        self.fractional_120 = Some(scale_120);
+    }
+
+    /// Point the viewporter destination rectangle at the logical size, so
+    /// fractional-scale compositors present a crisp, correctly sized buffer.
+    pub fn set_destination(&self, logical_width: i32, logical_height: i32) {
+        if let Some(viewport) = &self.viewport {
+            viewport.set_destination(logical_width, logical_height);
+        }
+    }
+}
+
+impl Dispatch<WpFractionalScaleManagerV1, ()> for Backend {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WpFractionalScaleManagerV1,
+        _event: <WpFractionalScaleManagerV1 as wayland_client::Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<WpFractionalScaleV1, WlSurface> for Backend {
+    fn event(
+        state: &mut Self,
+        _proxy: &WpFractionalScaleV1,
+        event: FractionalScaleEvent,
+        surface: &WlSurface,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        let FractionalScaleEvent::PreferredScale { scale } = event else {
+            return;
+        };
+
+        state.fractional_scale_changed(surface, scale, qh);
+    }
+}
+
+impl Dispatch<WpViewporter, ()> for Backend {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WpViewporter,
+        _event: <WpViewporter as wayland_client::Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<WpViewport, ()> for Backend {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WpViewport,
+        _event: <WpViewport as wayland_client::Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Scale;
+
+    #[test]
+    fn default_factor_is_one() {
+        assert_eq!(Scale::default().factor(), 1.0);
+    }
+
+    #[test]
+    fn integer_factor_without_fractional() {
+        let mut scale = Scale::default();
+        scale.set_integer(2);
+        assert_eq!(scale.factor(), 2.0);
+        assert!(!scale.is_fractional());
+    }
+
+    #[test]
+    fn fractional_takes_priority_over_integer() {
+        let mut scale = Scale::default();
+        scale.set_integer(2);
+        scale.set_fractional(150);
+        assert_eq!(scale.factor(), 1.25);
+        assert!(scale.is_fractional());
+    }
+}
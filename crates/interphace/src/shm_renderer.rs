@@ -0,0 +1,95 @@
+use skia_safe::{AlphaType, Canvas, ColorType as SkiaColorType, ImageInfo, Surface as SkiaSurface};
+use smithay_client_toolkit::shm::{slot::SlotPool, Shm};
+use wayland_client::protocol::{wl_shm, wl_surface::WlSurface};
+
+use crate::renderer::{DamageRect, RenderTarget};
+
+/// CPU fallback renderer for sessions without a usable GL context: double
+/// buffers through a `wl_shm` pool and paints directly into a Skia raster
+/// surface wrapping the pool's mapped memory. Buffer recycling is handled
+/// by `SlotPool` itself, which only hands out a slot once the compositor
+/// has released it.
+pub struct ShmRenderer {
+    wl_surface: WlSurface,
+    pool: SlotPool,
+    width: i32,
+    height: i32,
+    stride: i32,
+}
+
+impl ShmRenderer {
+    pub fn new(shm: &Shm, wl_surface: WlSurface, width: u32, height: u32) -> Self {
+        let stride = width.max(1) as i32 * 4;
+        let pool = SlotPool::new((stride as usize * height.max(1) as usize) * 2, shm)
+            .expect("Failed to create wl_shm pool");
+
+        Self {
+            wl_surface,
+            pool,
+            width: width as i32,
+            height: height as i32,
+            stride,
+        }
+    }
+}
+
+impl RenderTarget for ShmRenderer {
+    fn resize(&mut self, width: u32, height: u32) {
+        self.width = width as i32;
+        self.height = height as i32;
+        self.stride = self.width.max(1) * 4;
+
+        // The pool is double-buffered, so it must fit two buffers of the
+        // new size; `resize` is a no-op if it's already large enough.
+        let required = self.stride as usize * height.max(1) as usize * 2;
+        let _ = self.pool.resize(required);
+    }
+
+    fn present(&mut self, damage: &[DamageRect], paint: &mut dyn FnMut(&Canvas)) {
+        let (buffer, pixels) = self
+            .pool
+            .create_buffer(
+                self.width,
+                self.height,
+                self.stride,
+                wl_shm::Format::Argb8888,
+            )
+            .expect("Failed to create SHM buffer");
+
+        let image_info = ImageInfo::new(
+            (self.width, self.height),
+            SkiaColorType::BGRA8888,
+            AlphaType::Premul,
+            None,
+        );
+
+        // SAFETY: `pixels` is a mutable view into this buffer's slot of the
+        // pool, valid for at least as long as `raster_surface` below, which
+        // is dropped before this function returns.
+        let mut raster_surface =
+            unsafe { SkiaSurface::new_raster_direct(&image_info, pixels, Some(self.stride as usize), None) }
+                .expect("Failed to create SHM raster surface");
+
+        paint(raster_surface.canvas());
+
+        let rects: Vec<DamageRect> = if damage.is_empty() {
+            vec![DamageRect {
+                x: 0,
+                y: 0,
+                width: self.width,
+                height: self.height,
+            }]
+        } else {
+            damage.to_vec()
+        };
+
+        buffer
+            .attach_to(&self.wl_surface)
+            .expect("Failed to attach SHM buffer");
+        for rect in &rects {
+            self.wl_surface
+                .damage_buffer(rect.x, rect.y, rect.width, rect.height);
+        }
+        self.wl_surface.commit();
+    }
+}
@@ -0,0 +1,79 @@
+use xkbcommon::xkb::{
+    compose::{FeedResult, State as ComposeState, Status as ComposeStatus, Table as ComposeTable},
+    Context, Keysym, CONTEXT_NO_FLAGS,
+};
+
+/// Resolves composed UTF-8 text (dead keys, multi-key sequences such as
+/// `´` + `e` -> `é`) from the keysyms the compositor reports, layered on top
+/// of the per-key UTF-8 smithay-client-toolkit already decodes from the xkb
+/// keymap.
+pub struct TextInput {
+    compose_state: Option<ComposeState>,
+}
+
+impl TextInput {
+    pub fn new() -> Self {
+        let context = Context::new(CONTEXT_NO_FLAGS);
+        let locale = std::env::var("LANG").unwrap_or_else(|_| "C".to_string());
+
+        let compose_state = ComposeTable::new_from_locale(
+            &context,
+            &std::ffi::CString::new(locale).unwrap_or_default(),
+            xkbcommon::xkb::compose::COMPILE_NO_FLAGS,
+        )
+        .ok()
+        .map(|table| ComposeState::new(&table, xkbcommon::xkb::compose::STATE_NO_FLAGS));
+
+        Self { compose_state }
+    }
+
+    /// Feed a freshly pressed keysym through the compose state machine.
+    /// Returns the resolved text once a sequence composes; falls back to the
+    /// plain per-key UTF-8 when no compose table is available or no sequence
+    /// is in progress.
+    pub fn feed(&mut self, keysym: Keysym, fallback_utf8: Option<&str>) -> Option<String> {
+        let Some(compose_state) = self.compose_state.as_mut() else {
+            return fallback_utf8.map(str::to_string);
+        };
+
+        if compose_state.feed(keysym) != FeedResult::Accepted {
+            return fallback_utf8.map(str::to_string);
+        }
+
+        match compose_state.status() {
+            ComposeStatus::Composed => compose_state.utf8(),
+            ComposeStatus::Composing => None,
+            ComposeStatus::Cancelled | ComposeStatus::Nothing => {
+                compose_state.reset();
+                fallback_utf8.map(str::to_string)
+            }
+        }
+    }
+}
+
+impl Default for TextInput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xkbcommon::xkb::keysyms::KEY_a;
+
+    #[test]
+    fn plain_key_falls_back_to_per_key_utf8() {
+        let mut text_input = TextInput::new();
+        assert_eq!(
+            text_input.feed(Keysym::from(KEY_a), Some("a")),
+            Some("a".to_string())
+        );
+    }
+
+    #[test]
+    fn no_fallback_yields_none_outside_a_sequence() {
+        let mut text_input = TextInput::new();
+        assert_eq!(text_input.feed(Keysym::from(KEY_a), None), None);
+    }
+}
@@ -1,46 +1,207 @@
 use skia_safe::{Color, Paint};
-use smithay_client_toolkit::{shell::{xdg::{window::Window as XdgWindow}, WaylandSurface}};
-use wayland_client::{QueueHandle};
+use smithay_client_toolkit::shell::{xdg::window::Window as XdgWindow, WaylandSurface};
+use wayland_client::QueueHandle;
 
-use crate::{graphics_context::GraphicsContext, application::Application, backend::Backend};
+use crate::{
+    application::Application,
+    backend::Backend,
+    error::PhrameError,
+    input::InputEvent,
+    renderer::{clamp_to_surface, DamageRect, RenderTarget},
+    scale::Scale,
+};
 
 pub struct Window {
-    graphics_context: GraphicsContext,
-    pub xdg_window: XdgWindow
+    renderer: Box<dyn RenderTarget>,
+    pub xdg_window: XdgWindow,
+    scale: Scale,
+    logical_size: (u32, u32),
+    buffer_size: (i32, i32),
+    damage: Vec<DamageRect>,
+    full_damage: bool,
+    /// Set whenever something would change what's on screen; cleared once a
+    /// frame has actually been painted for it.
+    needs_redraw: bool,
+    /// Whether a `wl_surface.frame` callback is outstanding. While true,
+    /// `request_redraw` just marks the window dirty instead of painting
+    /// immediately, so repaints are paced to the compositor's refresh rate.
+    awaiting_frame: bool,
 }
 
 impl Window {
-    pub fn new(application: &Application) -> Self {
+    pub fn new(application: &Application) -> Result<Self, PhrameError> {
         let wl_surface = application.backend.create_surface();
+
+        let mut scale = Scale::default();
+        application
+            .backend
+            .bind_scale(&mut scale, &wl_surface, (100, 100));
+
+        let renderer = application.backend.create_renderer(&wl_surface, 100, 100);
+
         let xdg_window = application.backend.create_xdg_window(wl_surface);
         xdg_window.set_title("Interphace");
         xdg_window.commit();
 
-        let graphics_context = GraphicsContext::new(&xdg_window);
+        Ok(Self {
+            renderer,
+            xdg_window,
+            scale,
+            logical_size: (100, 100),
+            buffer_size: (100, 100),
+            damage: Vec::new(),
+            // The first frame has nothing to diff against, so it always
+            // repaints in full.
+            full_damage: true,
+            needs_redraw: true,
+            awaiting_frame: false,
+        })
+    }
+}
+
+impl Window {
+    /// Handle an `xdg_surface` configure: the new size is in logical
+    /// (surface-local) coordinates, so it must be multiplied by the current
+    /// scale factor to get the buffer size the renderer draws at.
+    pub fn configure_size(&mut self, logical_width: u32, logical_height: u32) {
+        self.logical_size = (logical_width, logical_height);
+        self.apply_scale();
+    }
+
+    /// Called once this surface's integer or fractional scale changes.
+    pub fn scale_changed(&mut self) {
+        self.apply_scale();
+    }
+
+    pub fn scale(&self) -> &Scale {
+        &self.scale
+    }
+
+    pub fn scale_mut(&mut self) -> &mut Scale {
+        &mut self.scale
+    }
+
+    fn apply_scale(&mut self) {
+        let factor = self.scale.factor();
+        let (logical_width, logical_height) = self.logical_size;
+
+        let buffer_width = ((logical_width as f64) * factor).round().max(1.0) as u32;
+        let buffer_height = ((logical_height as f64) * factor).round().max(1.0) as u32;
+        self.renderer.resize(buffer_width, buffer_height);
+        self.buffer_size = (buffer_width as i32, buffer_height as i32);
+
+        self.scale
+            .set_destination(logical_width as i32, logical_height as i32);
 
-        Self {
-            graphics_context,
-            xdg_window
+        // The old damage history no longer matches the resized buffer.
+        self.damage.clear();
+        self.full_damage = true;
+        self.needs_redraw = true;
+    }
+
+    /// Report a buffer-local dirty rectangle for the next `draw`. Rectangles
+    /// reported between draws accumulate; a draw with none reported (and no
+    /// pending full-surface redraw) is skipped entirely.
+    pub fn damage(&mut self, rect: DamageRect) {
+        self.damage.push(rect);
+        self.needs_redraw = true;
+    }
+
+    /// Mark the window dirty. If no `wl_surface.frame` callback is currently
+    /// outstanding, paints right away (which, as part of committing that
+    /// frame, requests the next callback); otherwise just waits for
+    /// `frame_done` to pick it up so repaints stay paced to one per vsync.
+    pub fn request_redraw(&mut self, qh: &QueueHandle<Backend>) {
+        self.needs_redraw = true;
+        if !self.awaiting_frame {
+            self.draw(qh);
+        }
+    }
+
+    /// Called from `CompositorHandler::frame` once the compositor has
+    /// processed the frame this window committed. Paints the next one if
+    /// anything became dirty in the meantime.
+    pub fn frame_done(&mut self, qh: &QueueHandle<Backend>) {
+        self.awaiting_frame = false;
+        if self.needs_redraw {
+            self.draw(qh);
+        }
+    }
+
+    /// Handle an input event routed here because this window's surface has
+    /// keyboard or pointer focus. Returns whether it changed anything that
+    /// needs a redraw.
+    pub fn handle_input(&mut self, event: InputEvent) -> bool {
+        match event {
+            InputEvent::KeyPress { keysym, text } => {
+                println!("Window got key press: keysym={keysym:#x} text={text:?}");
+                false
+            }
+            InputEvent::KeyRelease { keysym } => {
+                println!("Window got key release: keysym={keysym:#x}");
+                false
+            }
+            InputEvent::PointerMotion { .. } => false,
+            InputEvent::PointerButton { button, pressed } => {
+                println!("Window got pointer button: {button:?} pressed={pressed}");
+                false
+            }
+            InputEvent::PointerAxis { .. } => false,
         }
     }
 }
 
 impl Window {
-    pub fn draw(&mut self, qh: &QueueHandle<Backend>) {
-        self.graphics_context.make_current();
-        
-        println!("Drawing window");
-        let canvas = self.graphics_context.skia_surface.canvas();
+    /// Paint and present one frame, requesting the next `wl_surface.frame`
+    /// callback as part of the same commit so the following repaint is
+    /// paced to the compositor's refresh rate. Prefer `request_redraw`/`frame_done` over calling this
+    /// directly; they keep `needs_redraw`/`awaiting_frame` consistent.
+    fn draw(&mut self, qh: &QueueHandle<Backend>) {
+        let (buffer_width, buffer_height) = self.buffer_size;
+
+        let rects: Vec<DamageRect> = if self.full_damage {
+            vec![DamageRect {
+                x: 0,
+                y: 0,
+                width: buffer_width,
+                height: buffer_height,
+            }]
+        } else {
+            std::mem::take(&mut self.damage)
+                .into_iter()
+                .map(|rect| clamp_to_surface(rect, buffer_width, buffer_height))
+                .collect()
+        };
+
+        if rects.is_empty() {
+            self.needs_redraw = false;
+            return;
+        }
 
-        let mut paint = Paint::default();
-        paint.set_color(Color::from_argb(150, 80, 10, 200));
+        println!("Drawing window ({} damage rect(s))", rects.len());
+        let scale = self.scale.factor() as f32;
+        let wl_surface = self.xdg_window.wl_surface();
 
-        canvas.clear(Color::from_argb(190, 0, 0, 0));
-        canvas.draw_circle((50.0, 50.0), 20.0, &paint);
+        // Requested as part of this same commit, so the compositor notifies
+        // us once *this* frame has been presented.
+        wl_surface.frame(qh, wl_surface.clone());
+        self.awaiting_frame = true;
 
+        self.renderer.present(&rects, &mut |canvas| {
+            canvas.save();
+            canvas.scale((scale, scale));
 
-        //self.window.wl_surface().frame(qh, self.window.wl_surface().clone());
-        self.graphics_context.skia_surface.flush_and_submit();
-        self.graphics_context.swap_buffers();
+            let mut paint = Paint::default();
+            paint.set_color(Color::from_argb(150, 80, 10, 200));
+
+            canvas.clear(Color::from_argb(190, 0, 0, 0));
+            canvas.draw_circle((50.0, 50.0), 20.0, &paint);
+
+            canvas.restore();
+        });
+
+        self.damage.clear();
+        self.full_damage = false;
+        self.needs_redraw = false;
     }
-}
\ No newline at end of file
+}
@@ -1,15 +1,45 @@
-use interphace::{application::{Application}, window::Window};
+use interphace::{
+    application::Application, drm_renderer::DrmRenderer, error::PhrameError,
+    renderer::RenderTarget, window::Window,
+};
+use skia_safe::{Canvas, Color, Paint};
 
 fn main() {
-    let mut application = Application::new();
-    let window = Window::new(&application);
-    application.backend.add_window(window);
-    
-    let window2 = Window::new(&application);
-    application.backend.add_window(window2);
+    match Application::new() {
+        Ok(mut application) => {
+            let window = Window::new(&application).expect("Failed to create window");
+            application.backend.add_window(window);
 
-    let window3 = Window::new(&application);
-    application.backend.add_window(window3);
+            let window2 = Window::new(&application).expect("Failed to create window");
+            application.backend.add_window(window2);
 
-    application.run();
+            let window3 = Window::new(&application).expect("Failed to create window");
+            application.backend.add_window(window3);
+
+            application.run();
+        }
+        Err(PhrameError::NoWaylandConnection(reason)) => {
+            println!("No Wayland compositor available ({reason}), falling back to DRM/KMS");
+            run_drm_standalone();
+        }
+        Err(err) => panic!("Failed to set up application backend: {err}"),
+    }
+}
+
+/// Compositor-less fallback selected when no Wayland connection could be
+/// established: renders straight to a DRM/KMS output instead, for kiosk/TTY
+/// sessions with no compositor running.
+fn run_drm_standalone() {
+    let mut renderer = DrmRenderer::new("/dev/dri/card0");
+    loop {
+        renderer.present(&[], &mut paint_frame);
+    }
+}
+
+fn paint_frame(canvas: &Canvas) {
+    let mut paint = Paint::default();
+    paint.set_color(Color::from_argb(150, 80, 10, 200));
+
+    canvas.clear(Color::from_argb(190, 0, 0, 0));
+    canvas.draw_circle((50.0, 50.0), 20.0, &paint);
 }